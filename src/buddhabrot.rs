@@ -1,25 +1,129 @@
 use nannou::prelude::*;
-use nannou::rand;
 use nannou::wgpu;
+use nannou::wgpu::util::{BufferInitDescriptor, DeviceExt};
+use nannou_egui::{egui, Egui};
+use std::num::NonZeroU64;
 
 // 画面サイズ / Image resolution
 const WINDOW_WIDTH: u32 = 1000;
 const WINDOW_HEIGHT: u32 = 1000;
 
-// Buddhabrot 計算パラメータ / Computation parameters
-// 1サンプル（複素数c）につき、最大何回反復するか / Maximum iteration count per sample (complex parameter c).
-const MAX_ITER: u32 = 10_000;
-
-// 1フレームでランダムに試す c の個数（多いほど早く濃くなるが重くなる）
-// Number of random c samples per frame. More samples = faster convergence but heavier CPU load.
-const SAMPLES_PER_FRAME: usize = 20_000;
+// Nebulabrot のチャンネルごとの脱出反復しきい値（短命→R, 中間→G, 長寿命→B）。
+// Per-channel escape thresholds for the Nebulabrot (short escapes → R, medium → G,
+// long-lived orbits → B). Each channel is accumulated into its own count region.
+const MAX_ITER_RGB: [u32; 3] = [1_000, 5_000, 20_000];
+
+// 1フレームでGPUが試す c の個数。GPU計算なのでCPU版より桁違いに増やせる。
+// Number of random c samples dispatched per frame. Running on the GPU lets us push
+// orders of magnitude more samples than the CPU path. Kept modest out of the box so a
+// single dispatch stays well under the OS GPU watchdog (TDR); the egui slider can raise
+// it on hardware that tolerates more per-frame work.
+const SAMPLES_PER_FRAME: u32 = 100_000;
+
+// コンピュートシェーダのワークグループサイズ（1次元）
+// Compute shader workgroup size (1D).
+const WORKGROUP_SIZE: u32 = 64;
+
+// 1 次元あたりのワークグループ数上限（wgpu の既定 max_compute_workgroups_per_dimension）。
+// これを超えるとバリデーションで弾かれるので、splat ディスパッチは Y 方向にタイル分割する。
+// Max workgroups per dimension (wgpu's default max_compute_workgroups_per_dimension).
+// Exceeding it fails validation, so the splat dispatch tiles across the Y dimension.
+const MAX_DISPATCH_DIM: u32 = 65535;
+
+// Metropolis–Hastings 重点サンプリングを有効にするか。
+// 有効時は各インボケーションが独立したマルコフ連鎖を回し、寄与の高い c を重点的に探る。
+// Enable Metropolis–Hastings importance sampling. When on, each invocation runs an
+// independent Markov chain that concentrates effort on high-contribution c values.
+const USE_METROPOLIS: bool = true;
+
+// 各インボケーションが回す連鎖のステップ数（MH 有効時のみ使用）
+// Markov-chain step count per invocation (used only when MH is enabled).
+const CHAIN_LENGTH: u32 = 100;
 
 // 複素平面のサンプリング範囲（Mandelbrot の定番領域）
 // Common Mandelbrot viewing region (we sample c from here).
-const RE_MIN: f64 = -2.0;
-const RE_MAX: f64 = 1.0;
-const IM_MIN: f64 = -1.5;
-const IM_MAX: f64 = 1.5;
+const RE_MIN: f32 = -2.0;
+const RE_MAX: f32 = 1.0;
+const IM_MIN: f32 = -1.5;
+const IM_MAX: f32 = 1.5;
+
+// トーンマップのカーブ選択 / Selectable tone-mapping curves.
+// ダイナミックレンジの扱いをループに埋め込まず、ここで切り替えられるようにする。
+// Keeps the dynamic-range handling configurable instead of baked into the loop.
+#[derive(Clone, Copy, PartialEq)]
+enum ToneCurve {
+    // log1p(count) / log1p(観測最大値) の対数カーブ
+    // Logarithmic log1p(count)/log1p(observed max) curve.
+    Log,
+    // 正規化後に gamma 補正をかける
+    // Gamma correction applied after normalization.
+    Gamma,
+    // 区分的なコントラスト強調（0.5 を境に暗部を沈め、明部を持ち上げる S 字カーブ）
+    // Piecewise contrast boost (S-curve around 0.5: deepens shadows, brightens highlights).
+    Piecewise,
+}
+
+impl ToneCurve {
+    fn as_u32(self) -> u32 {
+        match self {
+            ToneCurve::Log => 0,
+            ToneCurve::Gamma => 1,
+            ToneCurve::Piecewise => 2,
+        }
+    }
+}
+
+// 使用するトーンカーブとガンマ値 / Active tone curve and gamma value.
+const TONE_CURVE: ToneCurve = ToneCurve::Log;
+const TONE_GAMMA: f32 = 0.45;
+
+// 実行時に egui から調整できるパラメータ群。
+// 従来 const だった値を Model が持つランタイムフィールドへ移したもの。
+// Runtime-tweakable parameters edited live via egui; these were previously `const`s.
+struct Settings {
+    samples_per_frame: u32,
+    max_iter_rgb: [u32; 3],
+    use_metropolis: bool,
+    chain_length: u32,
+    re_min: f32,
+    re_max: f32,
+    im_min: f32,
+    im_max: f32,
+    curve: ToneCurve,
+    gamma: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            samples_per_frame: SAMPLES_PER_FRAME,
+            max_iter_rgb: MAX_ITER_RGB,
+            use_metropolis: USE_METROPOLIS,
+            chain_length: CHAIN_LENGTH,
+            re_min: RE_MIN,
+            re_max: RE_MAX,
+            im_min: IM_MIN,
+            im_max: IM_MAX,
+            curve: TONE_CURVE,
+            gamma: TONE_GAMMA,
+        }
+    }
+}
+
+impl Settings {
+    // 1フレームで起動するコンピュートインボケーション数。
+    // MH 有効時は各インボケーションが chain_length 回 splat するため、
+    // 総 splat 回数が揃うようにインボケーション数を割る。
+    // Number of compute invocations per frame. With MH each invocation splats
+    // chain_length times, so divide to keep the total splat count comparable.
+    fn invocations_per_frame(&self) -> u32 {
+        if self.use_metropolis {
+            (self.samples_per_frame / self.chain_length.max(1)).max(1)
+        } else {
+            self.samples_per_frame
+        }
+    }
+}
 
 // エントリポイント / Entry point
 // nannou アプリを起動する / Launch the nannou app.
@@ -27,206 +131,705 @@ pub fn run() {
     nannou::app(model).update(update).run();
 }
 
-// モデル / Model
-// 計算結果（counts）を保持し、RGBAへ変換してテクスチャへアップロードして表示する
-// Holds accumulation buffers (counts), converts them into RGBA, uploads to GPU texture, and displays it.
-struct Model {
-    texture: wgpu::Texture,
+// ディスパッチパラメータを GPU へ渡すためのユニフォーム。
+// std140 レイアウトに合わせて 16 バイト境界にそろえておく。
+// Uniform carrying dispatch parameters to the GPU, laid out on 16-byte boundaries so
+// it matches the WGSL std140 layout.
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct Params {
+    re_min: f32,
+    re_max: f32,
+    im_min: f32,
+    im_max: f32,
+    width: u32,
+    height: u32,
+    // この splat パスの脱出しきい値 / escape threshold for this splat pass.
+    max_iter: u32,
+    samples: u32,
+    // フレームごとに変わるシード（GPU側PRNGの撹拌に使う）
+    // Per-frame seed used to stir the GPU-side PRNG.
+    frame: u32,
+    // Metropolis–Hastings を使うか（0 = 一様サンプル, 1 = MH）
+    // Whether to use Metropolis–Hastings (0 = uniform, 1 = MH).
+    use_metropolis: u32,
+    // MH 連鎖のステップ数 / MH chain length.
+    chain_length: u32,
+    // 書き込み先チャンネル（0 = R, 1 = G, 2 = B）
+    // Destination channel (0 = R, 1 = G, 2 = B).
+    channel: u32,
+    // トーンカーブの種類と gamma 値（tonemap パスで使用）
+    // Tone-curve selector and gamma value (used by the tonemap pass).
+    curve: u32,
+    gamma: f32,
+    _pad0: u32,
+    _pad1: u32,
+}
+
+unsafe impl bytemuck::Pod for Params {}
+unsafe impl bytemuck::Zeroable for Params {}
+
+// splat パス: c をランダムサンプルし、発散軌道を atomicAdd で counts に加算する。
+// splat pass: sample random c, iterate, and atomicAdd escaping orbits into counts.
+const SPLAT_SHADER: &str = r#"
+struct Params {
+    re_min: f32,
+    re_max: f32,
+    im_min: f32,
+    im_max: f32,
+    width: u32,
+    height: u32,
+    max_iter: u32,
+    samples: u32,
+    frame: u32,
+    use_metropolis: u32,
+    chain_length: u32,
+    channel: u32,
+    curve: u32,
+    gamma: f32,
+    _pad0: u32,
+    _pad1: u32,
+};
+
+@group(0) @binding(0) var<storage, read_write> counts: array<atomic<u32>>;
+@group(0) @binding(1) var<uniform> params: Params;
+
+// 1/T の重みを整数アトミックで蓄積するための固定小数点スケール（MH パス専用）。
+// Fixed-point scale so the MH 1/T weight can be accumulated with integer atomics.
+// 一様パスはこのスケールを使わず重み 1 で splat する（下の sample_uniform を参照）。
+// The uniform path does not use this scale and splats with weight 1 (see sample_uniform).
+const WEIGHT_SCALE: f32 = 4096.0;
+
+// カウンタの上限。u32 アトミックは飽和加算できないため、加算後に atomicMin で頭打ちにして
+// 長時間の累積でもラップ（2³² 超で 0 付近へ巻き戻る黒点）を防ぐ。
+// Per-counter ceiling. u32 atomics have no saturating add, so we clamp with atomicMin
+// after each add to keep long-running accumulation from wrapping past 2^32 (which would
+// reset hot pixels to near-zero and produce black speckle).
+const COUNT_CEIL: u32 = 0x4000_0000u;
+
+// PCG ハッシュベースの軽量 PRNG / Lightweight PCG-hash based PRNG.
+fn pcg(state: ptr<function, u32>) -> u32 {
+    let old = *state;
+    *state = old * 747796405u + 2891336453u;
+    let word = ((old >> ((old >> 28u) + 4u)) ^ old) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn rand_f32(state: ptr<function, u32>) -> f32 {
+    return f32(pcg(state)) * (1.0 / 4294967296.0);
+}
 
-    // 各ピクセルのヒット回数（軌道が通った回数）を蓄積する
-    // Per-pixel hit counts (how many orbit points landed on each pixel).
-    counts: Vec<u32>,
+// Box–Muller で正規乱数を 1 つ得る（局所変異に使う）
+// One Gaussian sample via Box–Muller (used for local mutations).
+fn rand_gauss(state: ptr<function, u32>, sigma: f32) -> f32 {
+    let u1 = max(rand_f32(state), 1e-7);
+    let u2 = rand_f32(state);
+    return sigma * sqrt(-2.0 * log(u1)) * cos(6.2831853 * u2);
+}
 
-    // 表示用のRGBAバッファ（countsをトーンマップして作る）
-    // RGBA buffer to display (tone-mapped from counts).
-    rgba: Vec<u8>,
+// c の寄与 T(c): 発散する軌道のうち画面内に落ちる点の数。
+// Contribution T(c): number of in-bounds orbit points for an escaping c (0 otherwise).
+fn contribution(cr: f32, ci: f32) -> u32 {
+    var zr = 0.0;
+    var zi = 0.0;
+    var escaped = false;
+    var hits: u32 = 0u;
+    for (var n: u32 = 0u; n < params.max_iter; n = n + 1u) {
+        let zr2 = zr * zr - zi * zi + cr;
+        let zi2 = 2.0 * zr * zi + ci;
+        zr = zr2;
+        zi = zi2;
+        if (zr * zr + zi * zi > 4.0) {
+            escaped = true;
+            break;
+        }
+        let fx = (zr - params.re_min) / (params.re_max - params.re_min) * f32(params.width);
+        let fy = (zi - params.im_min) / (params.im_max - params.im_min) * f32(params.height);
+        if (fx >= 0.0 && fx < f32(params.width) && fy >= 0.0 && fy < f32(params.height)) {
+            hits = hits + 1u;
+        }
+    }
+    if (!escaped) {
+        return 0u;
+    }
+    return hits;
+}
 
-    // 正規化用に、counts の最大値を追跡しておく
-    // Track max count for normalization.
-    max_count: u32,
+// 軌道を再計算し、画面内の各点へ weight（固定小数点）を atomicAdd する。
+// 書き込み先はチャンネルごとのオフセット channel * width * height を足した領域。
+// Replay the orbit and atomicAdd `weight` (fixed-point) into each in-bounds point,
+// offset by channel * width * height to target this channel's region.
+fn splat_orbit(cr: f32, ci: f32, weight: u32) {
+    if (weight == 0u) {
+        return;
+    }
+    let base = params.channel * params.width * params.height;
+    var zr = 0.0;
+    var zi = 0.0;
+    for (var n: u32 = 0u; n < params.max_iter; n = n + 1u) {
+        let zr2 = zr * zr - zi * zi + cr;
+        let zi2 = 2.0 * zr * zi + ci;
+        zr = zr2;
+        zi = zi2;
+        if (zr * zr + zi * zi > 4.0) {
+            break;
+        }
+        let fx = (zr - params.re_min) / (params.re_max - params.re_min) * f32(params.width);
+        let fy = (zi - params.im_min) / (params.im_max - params.im_min) * f32(params.height);
+        let x = i32(fx);
+        let y = i32(fy);
+        if (x >= 0 && x < i32(params.width) && y >= 0 && y < i32(params.height)) {
+            let idx = base + u32(y) * params.width + u32(x);
+            let prev = atomicAdd(&counts[idx], weight);
+            // ラップ防止の頭打ち / clamp to the ceiling to avoid u32 wrap-around.
+            atomicMin(&counts[idx], COUNT_CEIL);
+            // このチャンネルの観測最大値を更新する（トーンマップの自動スケール用）。
+            // Track this channel's observed maximum for the tone map's auto-scaling.
+            let accumulated = min(prev + weight, COUNT_CEIL);
+            let max_idx = params.width * params.height * 3u + params.channel;
+            atomicMax(&counts[max_idx], accumulated);
+        }
+    }
+}
 
-    // rgba を更新したかどうか（本来は view で false に戻したい）
-    // Whether RGBA has been updated (ideally set back to false after upload).
-    dirty: bool,
+// 一様サンプリング版：発散したものだけを等重みで splat する。
+// Uniform sampling: splat escaping orbits with equal weight.
+fn sample_uniform(rng: ptr<function, u32>) {
+    let cr = params.re_min + rand_f32(rng) * (params.re_max - params.re_min);
+    let ci = params.im_min + rand_f32(rng) * (params.im_max - params.im_min);
+    if (contribution(cr, ci) > 0u) {
+        splat_orbit(cr, ci, 1u);
+    }
+}
+
+// Metropolis–Hastings 版：独立した連鎖を回し、1/T 重みで splat する。
+// Metropolis–Hastings: run an independent chain and splat with a 1/T weight.
+fn sample_metropolis(rng: ptr<function, u32>) {
+    // 寄与が正の c が見つかるまでランダムに探して連鎖を初期化する
+    // Seed the chain by searching random c until one has positive contribution.
+    var cr = 0.0;
+    var ci = 0.0;
+    var t_cur: u32 = 0u;
+    for (var tries: u32 = 0u; tries < 64u && t_cur == 0u; tries = tries + 1u) {
+        cr = params.re_min + rand_f32(rng) * (params.re_max - params.re_min);
+        ci = params.im_min + rand_f32(rng) * (params.im_max - params.im_min);
+        t_cur = contribution(cr, ci);
+    }
+    if (t_cur == 0u) {
+        return;
+    }
+
+    let sigma = 0.01 * (params.re_max - params.re_min);
+    for (var step: u32 = 0u; step < params.chain_length; step = step + 1u) {
+        // 確率 ~0.1 で大域ジャンプ、それ以外は小さなガウス局所変異
+        // ~0.1 global jump, otherwise a small Gaussian local mutation.
+        var pr: f32;
+        var pi: f32;
+        if (rand_f32(rng) < 0.1) {
+            pr = params.re_min + rand_f32(rng) * (params.re_max - params.re_min);
+            pi = params.im_min + rand_f32(rng) * (params.im_max - params.im_min);
+        } else {
+            pr = cr + rand_gauss(rng, sigma);
+            pi = ci + rand_gauss(rng, sigma);
+        }
+
+        let t_prop = contribution(pr, pi);
+
+        // 受理確率 min(1, T'/T)
+        // Acceptance probability min(1, T'/T).
+        var accept = false;
+        if (t_prop > 0u) {
+            let a = f32(t_prop) / f32(t_cur);
+            if (a >= 1.0 || rand_f32(rng) < a) {
+                accept = true;
+            }
+        }
+
+        if (accept) {
+            cr = pr;
+            ci = pi;
+            t_cur = t_prop;
+        }
+
+        // バイアス除去のため 1/T 重みで splat する（受理時は採択点、非受理時は現在点）
+        // Splat with a 1/T weight to remove the sampling bias.
+        let weight = u32(max(WEIGHT_SCALE / f32(t_cur), 1.0));
+        splat_orbit(cr, ci, weight);
+    }
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) gid: vec3<u32>,
+        @builtin(num_workgroups) nwg: vec3<u32>) {
+    // ディスパッチは Y 方向にもタイル分割されうるので、2 次元 ID を線形化する。
+    // The dispatch may be tiled across Y, so linearize the 2-D invocation id.
+    let i = gid.y * (nwg.x * 64u) + gid.x;
+    if (i >= params.samples) {
+        return;
+    }
+
+    // 各インボケーションごとに独立したシードを作る
+    // Derive an independent seed per invocation.
+    var rng: u32 = (i * 2654435761u) ^ (params.frame * 40503u) ^ 0x9e3779b9u;
+
+    if (params.use_metropolis == 1u) {
+        sample_metropolis(&rng);
+    } else {
+        sample_uniform(&rng);
+    }
+}
+"#;
+
+// tonemap パス: 3 本の count 領域を R/G/B に合成し、選択したカーブでトーンマップする。
+// tonemap pass: composite the three count regions into R/G/B and tone-map with the
+// selected curve.
+const TONEMAP_SHADER: &str = r#"
+struct Params {
+    re_min: f32,
+    re_max: f32,
+    im_min: f32,
+    im_max: f32,
+    width: u32,
+    height: u32,
+    max_iter: u32,
+    samples: u32,
+    frame: u32,
+    use_metropolis: u32,
+    chain_length: u32,
+    channel: u32,
+    curve: u32,
+    gamma: f32,
+    _pad0: u32,
+    _pad1: u32,
+};
+
+@group(0) @binding(0) var<storage, read> counts: array<u32>;
+@group(0) @binding(1) var<uniform> params: Params;
+@group(0) @binding(2) var out_tex: texture_storage_2d<rgba8unorm, write>;
+
+// 正規化済みの値 u (0..1) に選択したカーブを適用する。
+// Apply the selected curve to a normalized value u in 0..1.
+fn apply_curve(u: f32) -> f32 {
+    if (params.curve == 1u) {
+        // gamma
+        return pow(clamp(u, 0.0, 1.0), params.gamma);
+    } else if (params.curve == 2u) {
+        // 区分的コントラスト / piecewise contrast
+        let c = clamp(u, 0.0, 1.0);
+        if (c < 0.5) {
+            return 2.0 * c * c;
+        }
+        return 1.0 - 2.0 * (1.0 - c) * (1.0 - c);
+    }
+    // log は counts 側で既に log 正規化済みなのでそのまま
+    // For log the value is already log-normalized upstream; pass through.
+    return clamp(u, 0.0, 1.0);
+}
+
+// raw count を観測最大値で log 正規化してからカーブを適用する。
+// norm は splat パスが atomicMax で蓄積した実測の最大カウントから導くので、
+// フレーム間の累積量や重みスケール（WEIGHT_SCALE）の違いに自動で追従する。
+// Log-normalize the raw count against the observed maximum, then apply the curve.
+// `norm` is derived from the actual max count the splat pass accumulated via atomicMax,
+// so it auto-scales with cross-frame accumulation and any weighting (WEIGHT_SCALE).
+fn channel_value(raw: u32, observed_max: u32) -> f32 {
+    let norm = log(f32(observed_max) + 1.0);
+    let u = log(f32(raw) + 1.0) / max(norm, 1e-6);
+    return apply_curve(u);
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) gid: vec3<u32>) {
+    if (gid.x >= params.width || gid.y >= params.height) {
+        return;
+    }
+    let plane = params.width * params.height;
+    let idx = gid.y * params.width + gid.x;
+
+    // 各チャンネルの観測最大値は counts 末尾の 3 スロットに入っている。
+    // The three observed per-channel maxima live in the trailing slots of `counts`.
+    let max_base = plane * 3u;
+    let r = channel_value(counts[idx], counts[max_base + 0u]);
+    let g = channel_value(counts[plane + idx], counts[max_base + 1u]);
+    let b = channel_value(counts[2u * plane + idx], counts[max_base + 2u]);
+
+    textureStore(out_tex, vec2<i32>(i32(gid.x), i32(gid.y)), vec4<f32>(r, g, b, 1.0));
+}
+"#;
+
+// モデル / Model
+// 計算はすべて GPU 上で行う。counts は 3 チャンネル分を連結したストレージバッファ上の
+// atomic<u32>、トーンマップ結果はストレージテクスチャへ書き込み、それを画面に貼る。
+// Everything is computed on the GPU. `count_buffer` holds three concatenated channel
+// regions of atomic<u32>; the tone-mapped result is written into a storage texture.
+struct Model {
+    texture: wgpu::Texture,
+    count_buffer: wgpu::Buffer,
+
+    // チャンネルごとのディスパッチパラメータと splat バインドグループ（R/G/B）
+    // Per-channel dispatch parameters and splat bind groups (R/G/B).
+    channel_params: [wgpu::Buffer; 3],
+    splat_bind_groups: [wgpu::BindGroup; 3],
+    splat_pipeline: wgpu::ComputePipeline,
+
+    tonemap_pipeline: wgpu::ComputePipeline,
+    tonemap_bind_group: wgpu::BindGroup,
+
+    // ランタイム調整用の設定と egui ハンドル
+    // Runtime settings and the egui handle.
+    settings: Settings,
+    egui: Egui,
+    // 次フレームで counts をゼロクリアする要求（egui のボタンで立てる）
+    // Request to zero the counts next frame (raised by the egui button).
+    clear_counts: bool,
+
+    frame: u32,
+}
+
+// 現在の設定からチャンネル c のパラメータを組み立てる。
+// Build the Params for channel `c` from the current settings.
+fn channel_params(s: &Settings, channel: u32, frame: u32) -> Params {
+    Params {
+        re_min: s.re_min,
+        re_max: s.re_max,
+        im_min: s.im_min,
+        im_max: s.im_max,
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        max_iter: s.max_iter_rgb[channel as usize],
+        samples: s.invocations_per_frame(),
+        frame,
+        use_metropolis: s.use_metropolis as u32,
+        chain_length: s.chain_length,
+        channel,
+        curve: s.curve.as_u32(),
+        gamma: s.gamma,
+        _pad0: 0,
+        _pad1: 0,
+    }
 }
 
 // 初期化 / Initialization
 fn model(app: &App) -> Model {
     // ウィンドウ生成（view 関数で描画する）
     // Create a window; rendering is done in `view`.
-    app.new_window()
+    let window_id = app
+        .new_window()
         .size(WINDOW_WIDTH, WINDOW_HEIGHT)
         .view(view)
+        .raw_event(raw_window_event)
         .build()
         .unwrap();
 
     let window = app.main_window();
+    let device = window.device();
+    let settings = Settings::default();
+
+    // egui のコントロールパネルをこのウィンドウに載せる
+    // Attach the egui control panel to this window.
+    let egui = Egui::from_window(&app.window(window_id).unwrap());
 
-    // 空のテクスチャをGPU側に作っておく。
-    // 毎フレーム、CPU側で作った rgba を upload_data でアップロードする。
-    // Create an empty GPU texture. Each frame we upload CPU-generated RGBA via `upload_data`.
+    // トーンマップの書き込み先 兼 描画元テクスチャ。
+    // Texture written by the tone-map pass and drawn to screen.
     let texture = wgpu::TextureBuilder::new()
         .size([WINDOW_WIDTH, WINDOW_HEIGHT])
-        .format(wgpu::TextureFormat::Rgba8UnormSrgb)
-        .usage(wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING)
-        .build(window.device());
-
-    // counts は 1ピクセルにつき 1要素、RGBA は 1ピクセルにつき 4要素
-    // `counts` has one u32 per pixel; `rgba` has 4 u8 per pixel.
-    let counts = vec![0u32; (WINDOW_WIDTH * WINDOW_HEIGHT) as usize];
-    let rgba = vec![0u8; (WINDOW_WIDTH * WINDOW_HEIGHT * 4) as usize];
+        .format(wgpu::TextureFormat::Rgba8Unorm)
+        .usage(wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING)
+        .build(device);
+    let texture_view = texture.view().build();
+
+    // counts は 3 チャンネル分 × 1ピクセル u32 に、末尾のチャンネル別観測最大値 3 スロットを
+    // 加えたもの。STORAGE で atomic アクセスする。
+    // Three channel regions (one u32 per pixel each) plus three trailing slots holding the
+    // per-channel observed maxima, bound as STORAGE for atomic access.
+    let count_len = (WINDOW_WIDTH * WINDOW_HEIGHT * 3 + 3) as u64;
+    let count_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("buddhabrot-counts"),
+        size: count_len * std::mem::size_of::<u32>() as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    // --- splat パイプライン / splat pipeline ---
+    let splat_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("buddhabrot-splat"),
+        source: wgpu::ShaderSource::Wgsl(SPLAT_SHADER.into()),
+    });
+    let splat_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("buddhabrot-splat-bgl"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: NonZeroU64::new(std::mem::size_of::<Params>() as u64),
+                },
+                count: None,
+            },
+        ],
+    });
+
+    // 各チャンネルのユニフォームとバインドグループを作る
+    // Build a uniform and bind group per channel.
+    let channel_params_buffers: [wgpu::Buffer; 3] = std::array::from_fn(|c| {
+        device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("buddhabrot-channel-params"),
+            contents: bytemuck::bytes_of(&channel_params(&settings, c as u32, 0)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        })
+    });
+    let splat_bind_groups: [wgpu::BindGroup; 3] = std::array::from_fn(|c| {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("buddhabrot-splat-bg"),
+            layout: &splat_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: count_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: channel_params_buffers[c].as_entire_binding(),
+                },
+            ],
+        })
+    });
+
+    let splat_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("buddhabrot-splat-pl"),
+        bind_group_layouts: &[&splat_layout],
+        push_constant_ranges: &[],
+    });
+    let splat_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("buddhabrot-splat-pipeline"),
+        layout: Some(&splat_pipeline_layout),
+        module: &splat_module,
+        entry_point: "main",
+    });
+
+    // --- tonemap パイプライン / tonemap pipeline ---
+    let tonemap_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("buddhabrot-tonemap"),
+        source: wgpu::ShaderSource::Wgsl(TONEMAP_SHADER.into()),
+    });
+    let tonemap_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("buddhabrot-tonemap-bgl"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: NonZeroU64::new(std::mem::size_of::<Params>() as u64),
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                },
+                count: None,
+            },
+        ],
+    });
+    let tonemap_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("buddhabrot-tonemap-bg"),
+        layout: &tonemap_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: count_buffer.as_entire_binding(),
+            },
+            // curve/gamma は全チャンネル共通なので R チャンネルのユニフォームを流用する
+            // curve/gamma are shared across channels, so reuse the R uniform here.
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: channel_params_buffers[0].as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(&texture_view),
+            },
+        ],
+    });
+    let tonemap_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("buddhabrot-tonemap-pl"),
+        bind_group_layouts: &[&tonemap_layout],
+        push_constant_ranges: &[],
+    });
+    let tonemap_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("buddhabrot-tonemap-pipeline"),
+        layout: Some(&tonemap_pipeline_layout),
+        module: &tonemap_module,
+        entry_point: "main",
+    });
 
     Model {
         texture,
-        counts,
-        rgba,
-        max_count: 1,
-        dirty: true,
-    }
-}
-
-// 更新（計算） / Update (CPU computation)
-// 毎フレーム、複素数 c をランダムに多数サンプリングし、
-// 発散したものだけ軌道(zの列)をcountsに加算していく（Buddhabrot）。
-// Each frame, sample many random complex parameters c.
-// For escaping ones only, accumulate their orbit points into counts (Buddhabrot).
-fn update(_app: &App, model: &mut Model, _update: Update) {
-    // 軌道（zの履歴）を入れるバッファ。毎サンプルで再利用してアロケを避ける。
-    // Orbit buffer reused per sample to avoid repeated allocations.
-    let mut orbit: Vec<(f64, f64)> = Vec::with_capacity(1024);
-
-    // 1フレームで SAMPLES_PER_FRAME 回だけ c を試す
-    // Try SAMPLES_PER_FRAME random c values per frame.
-    for _ in 0..SAMPLES_PER_FRAME {
-        // c を複素平面から一様にランダムサンプル
-        // Uniformly sample c from the complex plane region.
-        let cr: f64 = rand::random_range(RE_MIN, RE_MAX);
-        let ci: f64 = rand::random_range(IM_MIN, IM_MAX);
-
-        // 前回の軌道履歴をクリアして再利用
-        // Clear and reuse the orbit vector.
-        orbit.clear();
-
-        // z0 = 0 から始める（Mandelbrot反復）
-        // Start iteration from z0 = 0 (Mandelbrot iteration).
-        let mut zr = 0.0f64;
-        let mut zi = 0.0f64;
-
-        // 発散したかどうか（Buddhabrotでは“発散した点”の軌道だけを使う）
-        // Whether the orbit escaped (Buddhabrot uses orbits of escaping points).
-        let mut escaped = false;
-
-        // z_{n+1} = z_n^2 + c を反復
-        // Iterate z_{n+1} = z_n^2 + c.
-        for _ in 0..MAX_ITER {
-            // (zr + i*zi)^2 + (cr + i*ci)
-            let zr2 = zr * zr - zi * zi + cr;
-            let zi2 = 2.0 * zr * zi + ci;
-            zr = zr2;
-            zi = zi2;
-
-            // |z|^2 > 4 なら発散とみなす（脱出半径2）
-            // Escape test: if |z|^2 > 4, the orbit escapes (escape radius 2).
-            if zr * zr + zi * zi > 4.0 {
-                escaped = true;
-                break;
-            }
+        count_buffer,
+        channel_params: channel_params_buffers,
+        splat_bind_groups,
+        splat_pipeline,
+        tonemap_pipeline,
+        tonemap_bind_group,
+        settings,
+        egui,
+        clear_counts: false,
+        frame: 0,
+    }
+}
 
-            // 非発散のステップは軌道として蓄積（後で投影してcountsに加算）
-            // Store non-escaped steps into orbit for later projection.
-            // TODO: `if zr * zr + zi * zi > 4.0` の前に入れるべきか？
-            orbit.push((zr, zi));
-        }
+// egui にウィンドウの生イベントを渡す / Forward raw window events to egui.
+fn raw_window_event(_app: &App, model: &mut Model, event: &nannou::winit::event::WindowEvent) {
+    model.egui.handle_raw_event(event);
+}
 
-        // 発散した場合のみ、軌道点を2D画像座標に投影して counts を加算
-        // If escaped, project orbit points onto the image and increment counts.
-        if escaped {
-            for &(orbit_re, orbit_im) in &orbit {
-                // 複素平面座標 -> ピクセル座標へ線形変換
-                // Linear mapping from complex plane coordinates to pixel coordinates.
-                let x = ((orbit_re - RE_MIN) / (RE_MAX - RE_MIN) * (WINDOW_WIDTH as f64)) as i32;
-                let y = ((orbit_im - IM_MIN) / (IM_MAX - IM_MIN) * (WINDOW_HEIGHT as f64)) as i32;
-                // 範囲内なら counts に加算
-                // If within bounds, increment the hit count.
-                if (0..WINDOW_WIDTH as i32).contains(&x) && (0..WINDOW_HEIGHT as i32).contains(&y) {
-                    let idx = (y as u32 * WINDOW_WIDTH + x as u32) as usize;
-
-                    // saturating_add によりオーバーフローを防ぐ
-                    // Use saturating_add to prevent overflow.
-                    let v = model.counts[idx].saturating_add(1);
-                    model.counts[idx] = v;
-
-                    // 最大値を更新（正規化に使う）
-                    // Track max value for normalization.
-                    if v > model.max_count {
-                        model.max_count = v;
-                    }
-                }
+// 更新（ディスパッチの準備） / Update (prepare dispatch parameters)
+// egui パネルを更新し、要求があれば counts をクリアし、フレームごとのシードを含む
+// パラメータを各チャンネルのユニフォームへ書き込む。
+// Run the egui panel, clear the counts if requested, and write the parameters
+// (including the per-frame seed) into each channel's uniform.
+fn update(app: &App, model: &mut Model, update: Update) {
+    model.frame = model.frame.wrapping_add(1);
+
+    // --- egui コントロールパネル / egui control panel ---
+    {
+        let Model {
+            ref mut egui,
+            ref mut settings,
+            ref mut clear_counts,
+            ..
+        } = *model;
+
+        egui.set_elapsed_time(update.since_start);
+        let ctx = egui.begin_frame();
+        egui::Window::new("Buddhabrot").show(&ctx, |ui| {
+            ui.add(
+                egui::Slider::new(&mut settings.samples_per_frame, 10_000..=8_000_000)
+                    .logarithmic(true)
+                    .text("samples / frame"),
+            );
+            ui.checkbox(&mut settings.use_metropolis, "Metropolis-Hastings");
+            ui.add(egui::Slider::new(&mut settings.chain_length, 1..=1000).text("chain length"));
+
+            ui.separator();
+            ui.label("escape thresholds (R / G / B)");
+            ui.add(egui::Slider::new(&mut settings.max_iter_rgb[0], 100..=50_000).text("R"));
+            ui.add(egui::Slider::new(&mut settings.max_iter_rgb[1], 100..=50_000).text("G"));
+            ui.add(egui::Slider::new(&mut settings.max_iter_rgb[2], 100..=50_000).text("B"));
+
+            ui.separator();
+            ui.label("viewport");
+            ui.add(egui::Slider::new(&mut settings.re_min, -2.5..=0.0).text("re min"));
+            ui.add(egui::Slider::new(&mut settings.re_max, 0.0..=1.5).text("re max"));
+            ui.add(egui::Slider::new(&mut settings.im_min, -2.0..=0.0).text("im min"));
+            ui.add(egui::Slider::new(&mut settings.im_max, 0.0..=2.0).text("im max"));
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut settings.curve, ToneCurve::Log, "log");
+                ui.selectable_value(&mut settings.curve, ToneCurve::Gamma, "gamma");
+                ui.selectable_value(&mut settings.curve, ToneCurve::Piecewise, "piecewise");
+            });
+            ui.add(egui::Slider::new(&mut settings.gamma, 0.1..=2.0).text("gamma"));
+
+            ui.separator();
+            if ui.button("clear counts").clicked() {
+                *clear_counts = true;
             }
-        }
+        });
     }
 
-    // counts -> rgba（可視化） / Visualization (tone mapping)
-    // counts のダイナミックレンジが非常に広いので、logスケールにして見えるようにする
-    // counts have a huge dynamic range; use logarithmic scaling for visibility.
-    let max_c = model.max_count.max(1) as f64;
-    let denom = (max_c + 1.0).ln();
-
-    for i in 0..(WINDOW_WIDTH * WINDOW_HEIGHT) as usize {
-        let c = model.counts[i] as f64;
-
-        // log1p(count) / log1p(max) を 0..255 にスケール
-        // Scale log1p(count)/log1p(max) into 0..255.
-        let t = ((c + 1.0).ln() / denom * 255.0).clamp(0.0, 255.0) as u8;
-
-        // ここではグレースケール（R=G=B=t）
-        // Grayscale (R=G=B=t).
-        let o = i * 4;
-        model.rgba[o] = t;
-        model.rgba[o + 1] = t;
-        model.rgba[o + 2] = t;
-        model.rgba[o + 3] = 255;
+    let window = app.main_window();
+    let queue = window.queue();
+
+    // 「clear counts」ボタンが押されたら counts バッファをゼロで上書きする
+    // If the clear button was pressed, overwrite the counts buffer with zeros.
+    if model.clear_counts {
+        let zeros = vec![0u8; ((WINDOW_WIDTH * WINDOW_HEIGHT * 3 + 3) * 4) as usize];
+        queue.write_buffer(&model.count_buffer, 0, &zeros);
+        model.clear_counts = false;
     }
 
-    // rgba 更新済みフラグ
-    // Mark RGBA as updated.
-    model.dirty = true;
+    for c in 0..3u32 {
+        let params = channel_params(&model.settings, c, model.frame);
+        queue.write_buffer(
+            &model.channel_params[c as usize],
+            0,
+            bytemuck::bytes_of(&params),
+        );
+    }
 }
 
 // 描画 / Render
-// frame に対してテクスチャを描画する。
-// dirty の場合、CPU側 rgba を GPU テクスチャへ upload_data で転送してから描く。
-// Draw the texture to the frame.
-// If dirty, upload CPU RGBA to the GPU texture via upload_data before drawing.
+// フレームのコマンドエンコーダにチャンネルごとの splat パス 3 本と tonemap パスを積み、
+// 最後にトーンマップ結果のテクスチャをウィンドウ全体へ描く。
+// Record three per-channel splat passes plus the tonemap pass onto the frame encoder,
+// then draw the tone-mapped texture stretched across the window.
 fn view(app: &App, model: &Model, frame: Frame) {
-    // 背景を黒でクリア
-    // Clear background to black.
     frame.clear(BLACK);
 
-    // rgba を GPU テクスチャへ反映（upload_data を使う）
-    // Upload RGBA to the GPU texture using upload_data.
-    if model.dirty {
-        // コマンドエンコーダを取得し、そこにアップロード命令を積む
-        // Get a command encoder and record the upload commands.
+    {
         let mut encoder = frame.command_encoder();
 
-        // device を取得（frame が持つ device_queue_pair から）
-        // Obtain the device from the frame's device_queue_pair.
-        let device = frame.device_queue_pair().device();
-
-        // CPUの rgba バッファを GPU テクスチャにコピー
-        // Copy CPU RGBA buffer into the GPU texture.
-        model.texture.upload_data(device, &mut encoder, &model.rgba);
+        // splat パス: R/G/B それぞれのしきい値で 3 回ディスパッチする
+        // splat passes: dispatch once per channel with its own escape threshold.
+        // 総ワークグループ数が 1 次元の上限を超えうるので X×Y にタイル分割する
+        // （シェーダ側で num_workgroups を使って線形インデックスへ戻す）。
+        // The total workgroup count can exceed the per-dimension limit, so tile it across
+        // X and Y (the shader re-linearizes via num_workgroups).
+        let total_groups =
+            (model.settings.invocations_per_frame() + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE;
+        let groups_x = total_groups.min(MAX_DISPATCH_DIM).max(1);
+        let groups_y = (total_groups + groups_x - 1) / groups_x;
+        for c in 0..3 {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("buddhabrot-splat-pass"),
+            });
+            pass.set_pipeline(&model.splat_pipeline);
+            pass.set_bind_group(0, &model.splat_bind_groups[c], &[]);
+            pass.dispatch_workgroups(groups_x, groups_y, 1);
+        }
 
-        // NOTE:
-        // view は &Model なので dirty を false に戻せない。
-        // もし「更新があるときだけupload」したいなら、
-        // - raw_view を使う（&mut Model が取れるようにする）
-        // - もしくは update 側で “次フレームでupload済み扱い” にする
-        //
-        // view takes &Model so we cannot set dirty=false here.
-        // If you want upload-only-when-needed, consider using raw_view
-        // or manage the flag on update side.
+        // tonemap パス: 2D ディスパッチでピクセルごとに合成＆トーンマップ
+        // tonemap pass: composite and tone-map per pixel as a 2D dispatch.
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("buddhabrot-tonemap-pass"),
+            });
+            pass.set_pipeline(&model.tonemap_pipeline);
+            pass.set_bind_group(0, &model.tonemap_bind_group, &[]);
+            let gx = (WINDOW_WIDTH + 7) / 8;
+            let gy = (WINDOW_HEIGHT + 7) / 8;
+            pass.dispatch_workgroups(gx, gy, 1);
+        }
     }
 
     // テクスチャをウィンドウ全体に貼り付けて描画
@@ -234,4 +837,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
     let draw = app.draw();
     draw.texture(&model.texture).wh(app.window_rect().wh());
     draw.to_frame(app, &frame).unwrap();
+
+    // コントロールパネルを最前面に描画 / Draw the control panel on top.
+    model.egui.draw_to_frame(&frame).unwrap();
 }
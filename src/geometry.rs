@@ -0,0 +1,38 @@
+use nannou::prelude::*;
+
+/// Chaikin's corner-cutting curve subdivision.
+///
+/// One refinement pass replaces every segment `(Pi, Pi+1)` with two new points
+/// `Q = 0.75*Pi + 0.25*Pi+1` and `R = 0.25*Pi + 0.75*Pi+1`, keeping the first and
+/// last points fixed so the curve stays open. Repeating this `passes` times (2–4 is
+/// plenty) yields a smooth, corner-cut approximation of the input polyline.
+///
+/// Returns the input unchanged when it has fewer than three points or `passes` is 0.
+/// This is a general geometry helper other samples can share.
+pub fn chaikin(points: &[Point2], passes: u32) -> Vec<Point2> {
+    let mut current = points.to_vec();
+    if current.len() < 3 {
+        return current;
+    }
+
+    for _ in 0..passes {
+        let mut next = Vec::with_capacity(current.len() * 2);
+
+        // 両端は固定して開いた曲線にする / Keep the endpoints fixed for an open curve.
+        next.push(current[0]);
+
+        for pair in current.windows(2) {
+            let p0 = pair[0];
+            let p1 = pair[1];
+            let q = p0 * 0.75 + p1 * 0.25;
+            let r = p0 * 0.25 + p1 * 0.75;
+            next.push(q);
+            next.push(r);
+        }
+
+        next.push(current[current.len() - 1]);
+        current = next;
+    }
+
+    current
+}
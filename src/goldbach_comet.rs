@@ -1,4 +1,5 @@
 use nannou::prelude::*;
+use nannou_egui::{egui, Egui};
 use std::f32::consts::FRAC_PI_2;
 
 // Goldbach Comet
@@ -20,7 +21,12 @@ const PADDING_TOP: f32 = 50.0;
 
 const POINT_SIZE: f32 = 2.0; // プロットする四角点のサイズ
 const GRID_ALPHA: f32 = 0.18; // グリッド線の透明度（0.0〜1.0）
-const SHOW_GRID: bool = true; // グリッド表示の ON/OFF
+const SHOW_GRID: bool = true; // グリッド表示の初期 ON/OFF
+
+// X 軸グリッド分割数と Y 軸目盛り本数の初期値（egui で調整可能）
+// Initial X-grid division count and Y-tick count (adjustable via egui).
+const X_GRID_DIV: usize = 10;
+const DESIRED_Y_TICKS: u32 = 5;
 
 const LABEL_STEP: u32 = MAX / 5; // X 軸ラベル間隔
 
@@ -32,20 +38,61 @@ pub fn run() {
 // 描画に必要なデータを保持する
 // points は (n, g(n)) の点群
 struct Model {
-    points: Vec<(f32, f32)>, // (even_n, g(n))
-    max_count: u32,          // g(n) の最大値（Y スケール計算用）
+    points: Vec<(f32, f32, u8)>, // (even_n, g(n), residue class)
+    max_count: u32,              // g(n) の最大値（Y スケール計算用）
+
+    // 表示オプション（egui で切り替える）
+    // Display options toggled via egui.
+    show_grid: bool,
+    x_grid_div: usize,
+    desired_y_ticks: u32,
+    // Y 軸を対数スケールにするか / Whether to use a logarithmic Y axis.
+    log_y: bool,
+    egui: Egui,
+}
+
+// 偶数 n が属する帯クラス。コメットの 3 本の帯は n の小さな素因数で決まる。
+// spf（最小素因数表）で n を素因数分解し、帯構造を生む小さな奇素数 3・5 の有無で分類する:
+//   3 | n（6 で割り切れる）→ 最上段、3∤n かつ 5 | n → 中段、どちらでもない → 最下段。
+// Band class an even n belongs to. The comet's three bands are driven by n's small
+// prime factors. Factor n via the smallest-prime-factor table and group by the presence
+// of the small odd primes 3 and 5: multiples of 3 (i.e. of 6) form the top band, the
+// rest split by whether 5 divides them into the middle and lower bands.
+fn residue_class(n: u32, spf: &[u32]) -> u8 {
+    let (mut by_three, mut by_five) = (false, false);
+    let mut m = n;
+    while m > 1 {
+        let p = spf[m as usize];
+        match p {
+            3 => by_three = true,
+            5 => by_five = true,
+            _ => {}
+        }
+        while m % p == 0 {
+            m /= p;
+        }
+    }
+    match (by_three, by_five) {
+        (true, _) => 0,      // 3 | n: 最上段の帯 / top band
+        (false, true) => 1,  // 3∤n, 5 | n: 中段 / middle band
+        (false, false) => 2, // どちらでもない / neither
+    }
 }
 
 fn model(app: &App) -> Model {
-    app.new_window()
+    let window_id = app
+        .new_window()
         .size(WINDOW_WIDTH, WINDOW_HEIGHT)
         .title("Goldbach Comet")
         .view(view)
+        .raw_event(raw_window_event)
         .build()
         .unwrap();
+    let egui = Egui::from_window(&app.window(window_id).unwrap());
 
-    // 素数表（エラトステネス）を先に用意
+    // 素数表（エラトステネス）と最小素因数表を先に用意
     let is_prime = sieve(MAX);
+    let spf = smallest_prime_factors(MAX);
 
     // 初期化時に全点を計算・キャッシュしておき、
     // 描画側を軽くする
@@ -55,15 +102,44 @@ fn model(app: &App) -> Model {
     for n in (START..=MAX).step_by(STEP as usize) {
         let c = goldbach_pairs_count(n, &is_prime);
         max_count = max_count.max(c);
-        points.push((n as f32, c as f32));
+        points.push((n as f32, c as f32, residue_class(n, &spf)));
     }
 
-    Model { points, max_count }
+    Model {
+        points,
+        max_count,
+        show_grid: SHOW_GRID,
+        x_grid_div: X_GRID_DIV,
+        desired_y_ticks: DESIRED_Y_TICKS,
+        log_y: false,
+        egui,
+    }
 }
 
-fn update(_app: &App, _model: &mut Model, _update: Update) {
-    // 動的更新は不要
-    // 静止画なので何もしない
+/// egui にウィンドウの生イベントを渡す / Forward raw window events to egui.
+fn raw_window_event(_app: &App, model: &mut Model, event: &nannou::winit::event::WindowEvent) {
+    model.egui.handle_raw_event(event);
+}
+
+fn update(_app: &App, model: &mut Model, update: Update) {
+    // 点群は静止なので、egui のコントロールパネルだけを更新する
+    // The scatter is static, so only the egui control panel updates here.
+    let Model {
+        ref mut egui,
+        ref mut show_grid,
+        ref mut x_grid_div,
+        ref mut desired_y_ticks,
+        ref mut log_y,
+        ..
+    } = *model;
+    egui.set_elapsed_time(update.since_start);
+    let ctx = egui.begin_frame();
+    egui::Window::new("Goldbach Comet").show(&ctx, |ui| {
+        ui.checkbox(show_grid, "show grid");
+        ui.checkbox(log_y, "log Y axis");
+        ui.add(egui::Slider::new(x_grid_div, 2..=40).text("x grid divisions"));
+        ui.add(egui::Slider::new(desired_y_ticks, 2..=20).text("y ticks"));
+    });
 }
 
 /// エラトステネスの篩: 0..=limit の素数フラグを返す
@@ -90,6 +166,28 @@ fn sieve(limit: u32) -> Vec<bool> {
     is_prime
 }
 
+/// 最小素因数表: spf[k] = k の最小の素因数（k>=2）。エラトステネス式の篩で構築する。
+/// Smallest-prime-factor table: spf[k] is the least prime dividing k (for k>=2).
+fn smallest_prime_factors(limit: u32) -> Vec<u32> {
+    let n = limit as usize;
+    let mut spf = vec![0u32; n + 1];
+    let mut i = 2usize;
+    while i <= n {
+        if spf[i] == 0 {
+            // i は素数。i の倍数のうち未設定のものに i を記録する。
+            let mut k = i;
+            while k <= n {
+                if spf[k] == 0 {
+                    spf[k] = i as u32;
+                }
+                k += i;
+            }
+        }
+        i += 1;
+    }
+    spf
+}
+
 /// 偶数 n のゴールドバッハ分割数 g(n)
 /// p + q = n（p, q は素数、p<=q）を数える（順序は数えない）
 fn goldbach_pairs_count(n: u32, is_prime: &[bool]) -> u32 {
@@ -121,12 +219,22 @@ fn view(app: &App, model: &Model, frame: Frame) {
     // データ範囲（ワールド座標）
     let x_min = START as f32;
     let x_max = MAX as f32;
-    let y_min = 0.0f32;
     let raw_y_max = (model.max_count.max(1)) as f32;
-    let desired_y_ticks = 5;
-    let y_step = nice_tick_step(raw_y_max - y_min, desired_y_ticks);
-    let y_max = (raw_y_max / y_step).ceil() * y_step;
-    let y_tick_count = ((y_max - y_min) / y_step).round().max(1.0) as u32;
+    let log_y = model.log_y;
+
+    // Y 軸の範囲と目盛り。対数軸のときはデケード（10 の冪）刻みで配置する。
+    // Y-axis range and ticks. On a log axis ticks fall on decades (powers of 10).
+    let (y_min, y_max, y_step, y_tick_count) = if log_y {
+        let top_decade = raw_y_max.log10().ceil().max(1.0);
+        let y_max = 10f32.powf(top_decade);
+        (1.0f32, y_max, 0.0f32, top_decade as u32)
+    } else {
+        let y_min = 0.0f32;
+        let y_step = nice_tick_step(raw_y_max - y_min, model.desired_y_ticks);
+        let y_max = (raw_y_max / y_step).ceil() * y_step;
+        let y_tick_count = ((y_max - y_min) / y_step).round().max(1.0) as u32;
+        (y_min, y_max, y_step, y_tick_count)
+    };
 
     // 軸・ラベル描画
     draw.line()
@@ -158,8 +266,16 @@ fn view(app: &App, model: &Model, frame: Frame) {
         .font_size(16);
 
     // グリッドと目盛り
-    if SHOW_GRID {
-        draw_grid(&draw, left, right, bottom, top, 10, y_tick_count as usize);
+    if model.show_grid {
+        draw_grid(
+            &draw,
+            left,
+            right,
+            bottom,
+            top,
+            model.x_grid_div,
+            y_tick_count as usize,
+        );
     }
     draw_ticks(
         &draw,
@@ -173,20 +289,32 @@ fn view(app: &App, model: &Model, frame: Frame) {
         y_max,
         y_step,
         y_tick_count,
+        log_y,
     );
 
     // 点群をワールド座標 -> 画面座標へマッピングして描画
-    for &(x, y) in &model.points {
+    // 剰余類ごとに色相を変え、コメットの帯構造を見えるようにする
+    // Map points to screen space; hue by residue class so the comet bands stand out.
+    for &(x, y, class) in &model.points {
         let px = map_range(x, x_min, x_max, left, right);
-        let py = map_range(y, y_min, y_max, bottom, top);
+        let py = map_y(y, y_min, y_max, bottom, top, log_y);
+
+        let hue = match class {
+            0 => 0.02, // 6 で割り切れる / divisible by 6
+            1 => 0.38,
+            _ => 0.6,
+        };
 
         draw.rect()
             .x_y(px, py)
             .w_h(POINT_SIZE, POINT_SIZE)
-            .color(WHITE);
+            .hsl(hue, 0.8, 0.6);
     }
 
     draw.to_frame(app, &frame).unwrap();
+
+    // コントロールパネルを最前面に描画 / Draw the control panel on top.
+    model.egui.draw_to_frame(&frame).unwrap();
 }
 
 // 描画領域を等分して補助線を引く
@@ -221,6 +349,17 @@ fn draw_grid(
     }
 }
 
+// Y 値を画面座標へ写す。対数軸のときは log10 変換を通す。
+// Map a Y value to screen space, passing through a log10 transform on a log axis.
+fn map_y(value: f32, y_min: f32, y_max: f32, bottom: f32, top: f32, log_y: bool) -> f32 {
+    if log_y {
+        let lv = value.max(1.0).log10();
+        map_range(lv, y_min.max(1.0).log10(), y_max.log10(), bottom, top)
+    } else {
+        map_range(value, y_min, y_max, bottom, top)
+    }
+}
+
 fn draw_ticks(
     draw: &Draw,
     left: f32,
@@ -233,6 +372,7 @@ fn draw_ticks(
     y_max: f32,
     y_step: f32,
     y_ticks: u32,
+    log_y: bool,
 ) {
     let x_min_u = x_min.ceil().max(0.0) as u32;
     let x_max_u = x_max.floor().max(0.0) as u32;
@@ -258,11 +398,16 @@ fn draw_ticks(
         v += LABEL_STEP;
     }
 
-    // Y 軸は切り上げた上限とキリの良い間隔で配置
+    // Y 軸の目盛り。対数軸はデケード（10 の冪）ごと、線形軸はキリの良い間隔で配置する。
+    // Y ticks: decades (powers of 10) on a log axis, nice steps on a linear axis.
     for i in 0..=y_ticks {
-        let value = y_min + y_step * i as f32;
-        let py = map_range(value, y_min, y_max, bottom, top);
-        let label = if y_step.fract().abs() < f32::EPSILON {
+        let value = if log_y {
+            10f32.powf(i as f32)
+        } else {
+            y_min + y_step * i as f32
+        };
+        let py = map_y(value, y_min, y_max, bottom, top, log_y);
+        let label = if log_y || y_step.fract().abs() < f32::EPSILON {
             format!("{:.0}", value)
         } else {
             format!("{:.2}", value)
@@ -1,43 +1,118 @@
+use crate::geometry::chaikin;
 use nannou::prelude::*;
+use nannou_egui::{egui, Egui};
 
 /// Entry point for this sample application.
 ///
 /// Initializes the nannou app with the `model`, `update`, and `view`
 /// functions and starts the main event loop.
 pub fn run() {
-    nannou::app(model).update(update).simple_window(view).run();
+    nannou::app(model).update(update).run();
 }
 
+/// Default maximum number of points kept in the trail.
+const DEFAULT_MAX_POINTS: usize = 800;
+
+/// Default number of Chaikin refinement passes for the smoothed trail.
+const DEFAULT_SMOOTHING_PASSES: u32 = 3;
+
 struct Model {
     points: Vec<Point2>,
+    /// Maximum trail length, adjustable live from the control panel.
+    max_points: usize,
+    /// Whether to render the trail as a smoothed polyline instead of dots.
+    smooth: bool,
+    /// Number of Chaikin corner-cutting passes applied to the trail.
+    smoothing_passes: u32,
+    /// Smoothed trail, recomputed each frame when `smooth` is on.
+    smoothed: Vec<Point2>,
+    egui: Egui,
 }
 
 fn model(app: &App) -> Model {
     app.set_loop_mode(LoopMode::RefreshSync);
-    Model { points: Vec::new() }
+    let window_id = app
+        .new_window()
+        .view(view)
+        .raw_event(raw_window_event)
+        .build()
+        .unwrap();
+    let egui = Egui::from_window(&app.window(window_id).unwrap());
+    Model {
+        points: Vec::new(),
+        max_points: DEFAULT_MAX_POINTS,
+        smooth: true,
+        smoothing_passes: DEFAULT_SMOOTHING_PASSES,
+        smoothed: Vec::new(),
+        egui,
+    }
+}
+
+/// Forward raw window events to egui.
+fn raw_window_event(_app: &App, model: &mut Model, event: &nannou::winit::event::WindowEvent) {
+    model.egui.handle_raw_event(event);
 }
 
-fn update(app: &App, model: &mut Model, _: Update) {
+fn update(app: &App, model: &mut Model, update: Update) {
+    // Control panel: trail length, smoothing toggle and passes, and a reset button.
+    {
+        let Model {
+            ref mut egui,
+            ref mut max_points,
+            ref mut points,
+            ref mut smooth,
+            ref mut smoothing_passes,
+            ..
+        } = *model;
+        egui.set_elapsed_time(update.since_start);
+        let ctx = egui.begin_frame();
+        egui::Window::new("Spiral").show(&ctx, |ui| {
+            ui.add(egui::Slider::new(max_points, 10..=4000).text("trail length"));
+            ui.checkbox(smooth, "smoothed");
+            ui.add(egui::Slider::new(smoothing_passes, 0..=5).text("smoothing passes"));
+            if ui.button("reset trail").clicked() {
+                points.clear();
+            }
+        });
+    }
+
     let t = app.time;
     let r = 200.0 + 50.0 * (t * 0.5).sin();
     let angle = t * 0.7;
     let x = r * angle.cos();
     let y = r * angle.sin();
     model.points.push(pt2(x, y));
-    if model.points.len() > 800 {
+    while model.points.len() > model.max_points {
         model.points.remove(0);
     }
+
+    // 最新点を加えた後に Chaikin 平滑化を走らせる
+    // Run Chaikin smoothing after pushing the newest point.
+    if model.smooth {
+        model.smoothed = chaikin(&model.points, model.smoothing_passes);
+    }
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
     let draw = app.draw();
     draw.background().color(BLACK);
     // let alpha = 0.6;
-    for (i, p) in model.points.iter().enumerate() {
-        let w = 2.0 + (i as f32 * 0.01);
-        let hue = (i as f32 / model.points.len().max(1) as f32) * 0.8;
-        draw.ellipse().xy(*p).radius(w).hsl(hue, 0.6, 0.5);
-        // .alpha(alpha);
+    if model.smooth && model.smoothed.len() >= 2 {
+        // Chaikin で平滑化した折れ線として描く
+        // Draw the Chaikin-smoothed trail as a polyline.
+        draw.polyline()
+            .weight(2.0)
+            .points(model.smoothed.iter().cloned())
+            .hsl(0.55, 0.6, 0.5);
+    } else {
+        for (i, p) in model.points.iter().enumerate() {
+            let w = 2.0 + (i as f32 * 0.01);
+            let hue = (i as f32 / model.points.len().max(1) as f32) * 0.8;
+            draw.ellipse().xy(*p).radius(w).hsl(hue, 0.6, 0.5);
+            // .alpha(alpha);
+        }
     }
     draw.to_frame(app, &frame).unwrap();
+
+    model.egui.draw_to_frame(&frame).unwrap();
 }
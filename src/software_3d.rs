@@ -0,0 +1,301 @@
+use nannou::prelude::*;
+use nannou::wgpu;
+
+// 画面サイズ / Image resolution
+const WINDOW_WIDTH: u32 = 800;
+const WINDOW_HEIGHT: u32 = 800;
+
+// 起動時に選ぶシェーディング方式 / Shading mode selected at startup.
+const SHADING: Shading = Shading::Gouraud;
+
+// 光の進む向き（ワールド空間、正規化前） / Direction the light travels in world space
+// (not unit length; normalized before use).
+const LIGHT_DIR: [f32; 3] = [-0.5, -1.0, -0.8];
+
+// エントリポイント / Entry point
+// nannou アプリを起動する / Launch the nannou app.
+pub fn run() {
+    nannou::app(model).update(update).run();
+}
+
+// シェーディング方式 / Shading mode.
+#[derive(Clone, Copy)]
+enum Shading {
+    // 面ごとに 1 色（面法線・光源の内積） / one color per face (face normal · light).
+    Flat,
+    // 頂点ごとに陰影を計算し、バリセントリックで補間 / per-vertex lit colors interpolated.
+    Gouraud,
+}
+
+// 三角形（頂点インデックス 3 つ） / A triangle as three vertex indices.
+type Tri = [usize; 3];
+
+// モデル / Model
+// CPU 側 rgba バッファを dirty 時に upload_data でテクスチャへ転送するフレームバッファを使い、
+// 隠面消去用に Z バッファを追加する。
+// Uses a CPU rgba framebuffer uploaded to the texture via upload_data when dirty, plus a
+// per-pixel Z-buffer for hidden-surface removal.
+struct Model {
+    texture: wgpu::Texture,
+
+    // フレームバッファ（RGBA） / Framebuffer (RGBA).
+    rgba: Vec<u8>,
+
+    // 各ピクセルの深度（小さいほど手前） / Per-pixel depth (smaller is nearer).
+    zbuffer: Vec<f32>,
+
+    // メッシュ頂点とその法線、三角形インデックス
+    // Mesh vertices, their normals, and triangle indices.
+    vertices: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    tris: Vec<Tri>,
+
+    // 回転角（毎フレーム進める） / Rotation angle, advanced each frame.
+    angle: f32,
+
+    dirty: bool,
+}
+
+// 初期化 / Initialization
+fn model(app: &App) -> Model {
+    app.new_window()
+        .size(WINDOW_WIDTH, WINDOW_HEIGHT)
+        .title("Software 3D")
+        .view(view)
+        .build()
+        .unwrap();
+
+    let window = app.main_window();
+
+    let texture = wgpu::TextureBuilder::new()
+        .size([WINDOW_WIDTH, WINDOW_HEIGHT])
+        .format(wgpu::TextureFormat::Rgba8UnormSrgb)
+        .usage(wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING)
+        .build(window.device());
+
+    let rgba = vec![0u8; (WINDOW_WIDTH * WINDOW_HEIGHT * 4) as usize];
+    let zbuffer = vec![f32::INFINITY; (WINDOW_WIDTH * WINDOW_HEIGHT) as usize];
+
+    let (vertices, tris) = cube_mesh();
+    let normals = vertex_normals(&vertices, &tris);
+
+    Model {
+        texture,
+        rgba,
+        zbuffer,
+        vertices,
+        normals,
+        tris,
+        angle: 0.0,
+        dirty: true,
+    }
+}
+
+// 単位立方体メッシュ（頂点 8, 三角形 12） / Unit cube mesh (8 vertices, 12 triangles).
+fn cube_mesh() -> (Vec<Vec3>, Vec<Tri>) {
+    let vertices = vec![
+        vec3(-1.0, -1.0, -1.0),
+        vec3(1.0, -1.0, -1.0),
+        vec3(1.0, 1.0, -1.0),
+        vec3(-1.0, 1.0, -1.0),
+        vec3(-1.0, -1.0, 1.0),
+        vec3(1.0, -1.0, 1.0),
+        vec3(1.0, 1.0, 1.0),
+        vec3(-1.0, 1.0, 1.0),
+    ];
+    // 各三角形は外向き法線になるよう CCW（反時計回り）に巻いてある。
+    // これは面/頂点法線と、スクリーン空間のバックフェースカリング規約に一致する。
+    // Each triangle is wound CCW so its normal points outward, matching the face/vertex
+    // normals and the screen-space back-face cull convention.
+    let tris = vec![
+        [0, 2, 1],
+        [0, 3, 2], // -Z
+        [5, 7, 4],
+        [5, 6, 7], // +Z
+        [4, 3, 0],
+        [4, 7, 3], // -X
+        [1, 6, 5],
+        [1, 2, 6], // +X
+        [4, 1, 5],
+        [4, 0, 1], // -Y
+        [3, 6, 2],
+        [3, 7, 6], // +Y
+    ];
+    (vertices, tris)
+}
+
+// 隣接面の法線を平均して頂点法線を求める（Gouraud 用）。
+// Average adjacent face normals to obtain per-vertex normals (for Gouraud shading).
+fn vertex_normals(vertices: &[Vec3], tris: &[Tri]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::ZERO; vertices.len()];
+    for t in tris {
+        let a = vertices[t[0]];
+        let b = vertices[t[1]];
+        let c = vertices[t[2]];
+        let fn_ = (b - a).cross(c - a);
+        for &i in t {
+            normals[i] += fn_;
+        }
+    }
+    for n in normals.iter_mut() {
+        *n = n.normalize_or_zero();
+    }
+    normals
+}
+
+// 更新（CPU ラスタライズ） / Update (CPU rasterization)
+fn update(_app: &App, model: &mut Model, _update: Update) {
+    model.angle += 0.01;
+
+    // フレームバッファと Z バッファをクリア
+    // Clear the framebuffer and the Z-buffer.
+    for px in model.rgba.chunks_exact_mut(4) {
+        px.copy_from_slice(&[8, 8, 16, 255]);
+    }
+    for z in model.zbuffer.iter_mut() {
+        *z = f32::INFINITY;
+    }
+
+    // 変換チェーン: world -> view(look-at) -> perspective
+    // Transform chain: world -> view (look-at) -> perspective.
+    let world = Mat4::from_rotation_y(model.angle) * Mat4::from_rotation_x(model.angle * 0.6);
+    let view = Mat4::look_at_rh(vec3(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+    let aspect = WINDOW_WIDTH as f32 / WINDOW_HEIGHT as f32;
+    let proj = Mat4::perspective_rh(60.0_f32.to_radians(), aspect, 0.1, 100.0);
+    let mvp = proj * view * world;
+
+    // 法線はスケールなしの回転のみなので world の回転部分で変換できる
+    // Normals only undergo rotation (no scale), so the world matrix transforms them.
+    let normal_mat = Mat3::from_mat4(world);
+    let light = Vec3::from(LIGHT_DIR).normalize();
+
+    for t in &model.tris {
+        // 各頂点をクリップ空間へ / Transform each vertex to clip space.
+        let clip: Vec<Vec4> = t
+            .iter()
+            .map(|&i| mvp * model.vertices[i].extend(1.0))
+            .collect();
+
+        // near 面より後ろの頂点を含む三角形は捨てる（簡易クリップ）
+        // Drop triangles with any vertex behind the near plane (simple near cull).
+        if clip.iter().any(|c| c.w <= 0.0) {
+            continue;
+        }
+
+        // 透視除算してスクリーン座標と深度を求める
+        // Perspective divide to get screen coordinates and depth.
+        let mut screen = [Vec3::ZERO; 3];
+        for (k, c) in clip.iter().enumerate() {
+            let ndc = c.truncate() / c.w;
+            let sx = (ndc.x * 0.5 + 0.5) * WINDOW_WIDTH as f32;
+            let sy = (1.0 - (ndc.y * 0.5 + 0.5)) * WINDOW_HEIGHT as f32;
+            screen[k] = vec3(sx, sy, ndc.z);
+        }
+
+        // 面法線（ワールド空間） / Face normal in world space.
+        let wa = (world * model.vertices[t[0]].extend(1.0)).truncate();
+        let wb = (world * model.vertices[t[1]].extend(1.0)).truncate();
+        let wc = (world * model.vertices[t[2]].extend(1.0)).truncate();
+        let face_n = (wb - wa).cross(wc - wa).normalize_or_zero();
+
+        // 背面カリング（スクリーン上の向きで判定）
+        // Back-face culling based on screen-space winding. Screen y points down, so a
+        // front-facing CCW triangle has negative signed area here.
+        let area = edge(screen[0], screen[1], screen[2]);
+        if area >= 0.0 {
+            continue;
+        }
+
+        // 各頂点の陰影（Gouraud 用） / Per-vertex lit intensity (for Gouraud).
+        let vcol: [Vec3; 3] = std::array::from_fn(|k| {
+            let n = (normal_mat * model.normals[t[k]]).normalize_or_zero();
+            shade(n, light)
+        });
+        let flat_col = shade(face_n, light);
+
+        rasterize(model, screen, area, vcol, flat_col);
+    }
+
+    model.dirty = true;
+}
+
+// 拡散反射のみの単純な陰影（環境光 + ランバート）。
+// Simple diffuse-only shading (ambient + Lambert).
+fn shade(normal: Vec3, light: Vec3) -> Vec3 {
+    // light は光の進む向き（光源から面へ）なので -light と内積を取る
+    // `light` is the direction the light travels (source toward surface), so dot with -light.
+    let diff = normal.dot(-light).max(0.0);
+    let intensity = 0.15 + 0.85 * diff;
+    vec3(0.9, 0.55, 0.25) * intensity
+}
+
+// スクリーン空間の符号付き面積 ×2（バリセントリックの分母に使う）。
+// Twice the signed screen-space area (used as the barycentric denominator).
+fn edge(a: Vec3, b: Vec3, c: Vec3) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+// バリセントリック座標で三角形を塗りつぶし、Z バッファで隠面消去する。
+// Fill the triangle via barycentric coordinates with Z-buffered hidden-surface removal.
+fn rasterize(model: &mut Model, s: [Vec3; 3], area: f32, vcol: [Vec3; 3], flat_col: Vec3) {
+    let min_x = s.iter().map(|p| p.x).fold(f32::INFINITY, f32::min).floor().max(0.0) as i32;
+    let max_x = s.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max).ceil()
+        .min(WINDOW_WIDTH as f32 - 1.0) as i32;
+    let min_y = s.iter().map(|p| p.y).fold(f32::INFINITY, f32::min).floor().max(0.0) as i32;
+    let max_y = s.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max).ceil()
+        .min(WINDOW_HEIGHT as f32 - 1.0) as i32;
+
+    let inv_area = 1.0 / area;
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let p = vec3(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+
+            // バリセントリック重み / Barycentric weights.
+            let w0 = edge(s[1], s[2], p) * inv_area;
+            let w1 = edge(s[2], s[0], p) * inv_area;
+            let w2 = edge(s[0], s[1], p) * inv_area;
+            if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                continue;
+            }
+
+            // 深度を補間して Z テスト / Interpolate depth and run the Z-test.
+            let z = w0 * s[0].z + w1 * s[1].z + w2 * s[2].z;
+            let idx = (y as u32 * WINDOW_WIDTH + x as u32) as usize;
+            if z >= model.zbuffer[idx] {
+                continue;
+            }
+            model.zbuffer[idx] = z;
+
+            // シェーディング方式に応じて色を決める
+            // Pick the color according to the shading mode.
+            let col = match SHADING {
+                Shading::Flat => flat_col,
+                Shading::Gouraud => w0 * vcol[0] + w1 * vcol[1] + w2 * vcol[2],
+            };
+
+            let o = idx * 4;
+            model.rgba[o] = (col.x.clamp(0.0, 1.0) * 255.0) as u8;
+            model.rgba[o + 1] = (col.y.clamp(0.0, 1.0) * 255.0) as u8;
+            model.rgba[o + 2] = (col.z.clamp(0.0, 1.0) * 255.0) as u8;
+            model.rgba[o + 3] = 255;
+        }
+    }
+}
+
+// 描画 / Render
+// dirty の場合、CPU側 rgba を GPU テクスチャへ upload_data で転送してから描く。
+// If dirty, upload CPU RGBA to the GPU texture via upload_data before drawing.
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(BLACK);
+
+    if model.dirty {
+        let mut encoder = frame.command_encoder();
+        let device = frame.device_queue_pair().device();
+        model.texture.upload_data(device, &mut encoder, &model.rgba);
+    }
+
+    let draw = app.draw();
+    draw.texture(&model.texture).wh(app.window_rect().wh());
+    draw.to_frame(app, &frame).unwrap();
+}
@@ -0,0 +1,232 @@
+use nannou::prelude::*;
+use nannou::wgpu;
+
+// 画面サイズ / Image resolution
+const WINDOW_WIDTH: u32 = 1000;
+const WINDOW_HEIGHT: u32 = 1000;
+
+// 1フレームで反復する回数（多いほど早く濃くなる）
+// Iterations per frame (more = faster convergence of the density plot).
+const ITER_PER_FRAME: usize = 200_000;
+
+// 起動時に選ぶアトラクタ / Attractor selected at startup.
+const ATTRACTOR: Attractor = Attractor::DeJong;
+
+// エントリポイント / Entry point
+// nannou アプリを起動する / Launch the nannou app.
+pub fn run() {
+    nannou::app(model).update(update).run();
+}
+
+// アトラクタの種類。それぞれ固有のパラメータと漸化式を持つ。
+// Attractor family. Each variant carries its own parameters and recurrence.
+#[derive(Clone, Copy)]
+enum Attractor {
+    // de Jong: x' = sin(a*y) - cos(b*x), y' = sin(c*x) - cos(d*y)
+    DeJong,
+    // Clifford: x' = sin(a*y) + c*cos(a*x), y' = sin(b*x) + d*cos(b*y)
+    Clifford,
+    // Lorenz を (x, z) 平面へ投影したもの / the Lorenz system projected onto the (x, z) plane.
+    Lorenz,
+}
+
+// アトラクタの状態（2D マップは (x,y)、Lorenz は 3D なので z も持つ）
+// Attractor state: 2D maps use (x, y); Lorenz is 3D so it also carries z.
+#[derive(Clone, Copy)]
+struct State {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Attractor {
+    // 1ステップ進める / Advance the state by one step.
+    fn step(self, s: State) -> State {
+        match self {
+            Attractor::DeJong => {
+                // (a, b, c, d) は見栄えのする定番の組 / a well-known pleasing parameter set.
+                let (a, b, c, d) = (1.4, -2.3, 2.4, -2.1);
+                State {
+                    x: (a * s.y).sin() - (b * s.x).cos(),
+                    y: (c * s.x).sin() - (d * s.y).cos(),
+                    z: 0.0,
+                }
+            }
+            Attractor::Clifford => {
+                let (a, b, c, d) = (-1.4, 1.6, 1.0, 0.7);
+                State {
+                    x: (a * s.y).sin() + c * (a * s.x).cos(),
+                    y: (b * s.x).sin() + d * (b * s.y).cos(),
+                    z: 0.0,
+                }
+            }
+            Attractor::Lorenz => {
+                // 古典的な Lorenz パラメータを Euler 法で積分する
+                // Classic Lorenz parameters, integrated with a small Euler step.
+                let (sigma, rho, beta, dt) = (10.0, 28.0, 8.0 / 3.0, 0.005);
+                State {
+                    x: s.x + dt * (sigma * (s.y - s.x)),
+                    y: s.y + dt * (s.x * (rho - s.z) - s.y),
+                    z: s.z + dt * (s.x * s.y - beta * s.z),
+                }
+            }
+        }
+    }
+
+    // (x, y) をピクセル座標に写す際のワールド範囲（min, max）。
+    // World bounds (min, max) used to map the plotted coordinates to pixels.
+    fn bounds(self) -> (f64, f64, f64, f64) {
+        match self {
+            Attractor::DeJong | Attractor::Clifford => (-2.5, 2.5, -2.5, 2.5),
+            // Lorenz は (x, z) を描くので z の範囲に合わせる
+            // Lorenz plots (x, z), so match the z range.
+            Attractor::Lorenz => (-25.0, 25.0, 0.0, 50.0),
+        }
+    }
+
+    // 描画に使う 2 成分を取り出す（Lorenz は (x, z)）。
+    // Pick the two components to plot (Lorenz uses (x, z)).
+    fn plot_xy(self, s: State) -> (f64, f64) {
+        match self {
+            Attractor::Lorenz => (s.x, s.z),
+            _ => (s.x, s.y),
+        }
+    }
+
+    // 初期状態 / Initial state.
+    fn seed(self) -> State {
+        match self {
+            Attractor::Lorenz => State {
+                x: 0.1,
+                y: 0.0,
+                z: 0.0,
+            },
+            _ => State {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        }
+    }
+}
+
+// モデル / Model
+// 「CPU密度バッファ -> upload_data テクスチャ」構成で描画する。
+// Renders through a "CPU density buffer -> upload_data texture" pipeline.
+struct Model {
+    texture: wgpu::Texture,
+
+    // 各ピクセルの密度（軌道が通った回数）
+    // Per-pixel density (how many times the orbit landed on each pixel).
+    counts: Vec<u32>,
+
+    // 表示用 RGBA バッファ / RGBA buffer for display.
+    rgba: Vec<u8>,
+
+    // 正規化用の最大密度 / Max density used for normalization.
+    max_count: u32,
+
+    // 反復状態（前フレームから継続する）
+    // Iteration state, carried over from the previous frame.
+    state: State,
+
+    dirty: bool,
+}
+
+// 初期化 / Initialization
+fn model(app: &App) -> Model {
+    app.new_window()
+        .size(WINDOW_WIDTH, WINDOW_HEIGHT)
+        .title("Strange Attractor")
+        .view(view)
+        .build()
+        .unwrap();
+
+    let window = app.main_window();
+
+    let texture = wgpu::TextureBuilder::new()
+        .size([WINDOW_WIDTH, WINDOW_HEIGHT])
+        .format(wgpu::TextureFormat::Rgba8UnormSrgb)
+        .usage(wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING)
+        .build(window.device());
+
+    let counts = vec![0u32; (WINDOW_WIDTH * WINDOW_HEIGHT) as usize];
+    let rgba = vec![0u8; (WINDOW_WIDTH * WINDOW_HEIGHT * 4) as usize];
+
+    Model {
+        texture,
+        counts,
+        rgba,
+        max_count: 1,
+        state: ATTRACTOR.seed(),
+        dirty: true,
+    }
+}
+
+// 更新（計算） / Update (CPU computation)
+// 前フレームの状態から ITER_PER_FRAME 回反復し、各点を密度バッファへ加算する。
+// Iterate ITER_PER_FRAME times from the previous state and accumulate each point.
+fn update(_app: &App, model: &mut Model, _update: Update) {
+    let (x_min, x_max, y_min, y_max) = ATTRACTOR.bounds();
+    let mut s = model.state;
+
+    for _ in 0..ITER_PER_FRAME {
+        s = ATTRACTOR.step(s);
+        let (px, py) = ATTRACTOR.plot_xy(s);
+
+        // ワールド座標 -> ピクセル座標へ線形変換
+        // Linear mapping from world coordinates to pixel coordinates.
+        let x = ((px - x_min) / (x_max - x_min) * (WINDOW_WIDTH as f64)) as i32;
+        let y = ((py - y_min) / (y_max - y_min) * (WINDOW_HEIGHT as f64)) as i32;
+
+        if (0..WINDOW_WIDTH as i32).contains(&x) && (0..WINDOW_HEIGHT as i32).contains(&y) {
+            let idx = (y as u32 * WINDOW_WIDTH + x as u32) as usize;
+
+            // saturating_add によりオーバーフローを防ぐ
+            // Use saturating_add to prevent overflow.
+            let v = model.counts[idx].saturating_add(1);
+            model.counts[idx] = v;
+
+            if v > model.max_count {
+                model.max_count = v;
+            }
+        }
+    }
+
+    // 次フレームへ状態を引き継ぐ / Carry the state over to the next frame.
+    model.state = s;
+
+    // counts -> rgba（logトーンマップ） / Visualization (log tone mapping)
+    let max_c = model.max_count.max(1) as f64;
+    let denom = (max_c + 1.0).ln();
+
+    for i in 0..(WINDOW_WIDTH * WINDOW_HEIGHT) as usize {
+        let c = model.counts[i] as f64;
+        let t = ((c + 1.0).ln() / denom * 255.0).clamp(0.0, 255.0) as u8;
+
+        let o = i * 4;
+        model.rgba[o] = t;
+        model.rgba[o + 1] = t;
+        model.rgba[o + 2] = t;
+        model.rgba[o + 3] = 255;
+    }
+
+    model.dirty = true;
+}
+
+// 描画 / Render
+// dirty の場合、CPU側 rgba を GPU テクスチャへ upload_data で転送してから描く。
+// If dirty, upload CPU RGBA to the GPU texture via upload_data before drawing.
+fn view(app: &App, model: &Model, frame: Frame) {
+    frame.clear(BLACK);
+
+    if model.dirty {
+        let mut encoder = frame.command_encoder();
+        let device = frame.device_queue_pair().device();
+        model.texture.upload_data(device, &mut encoder, &model.rgba);
+    }
+
+    let draw = app.draw();
+    draw.texture(&model.texture).wh(app.window_rect().wh());
+    draw.to_frame(app, &frame).unwrap();
+}